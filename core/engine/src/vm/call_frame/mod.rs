@@ -11,12 +11,13 @@ use crate::{
     object::{JsFunction, JsObject},
     realm::Realm,
     vm::CodeBlock,
-    JsValue,
+    JsResult, JsValue,
 };
 use boa_ast::scope::BindingLocator;
 use boa_gc::{Finalize, Gc, Trace};
 use thin_vec::ThinVec;
 
+use super::resource_limiter::{resource_limit_exceeded_error, ResourceLimiter};
 use super::{ActiveRunnable, Registers, Vm};
 
 bitflags::bitflags! {
@@ -31,10 +32,28 @@ bitflags::bitflags! {
         const CONSTRUCT = 0b0000_0010;
 
         /// Does this [`CallFrame`] need to push registers on [`Vm::push_frame()`].
+        ///
+        /// Set for tail calls and resumed generator/async frames that reuse
+        /// an existing register window. Such a frame still counts as one
+        /// more call towards [`Vm`]'s configurable call-stack depth limit
+        /// (see [`crate::vm::stack_limit`]), even though it doesn't grow the
+        /// register stack the way a fresh frame does.
         const REGISTERS_ALREADY_PUSHED = 0b0000_0100;
 
         /// If the `this` value has been cached.
         const THIS_VALUE_CACHED = 0b0000_1000;
+
+        /// Has the generator or async function this [`CallFrame`] belongs to
+        /// already run to completion (returned, threw, or was awaited to its
+        /// conclusion)?
+        ///
+        /// Checked before honoring a resume request on the frame's
+        /// `async_generator_object`/`promise_capability` registers, so that
+        /// an illegal resume of an already-finished generator or awaited
+        /// async frame fails with a diagnosable
+        /// [`GeneratorAlreadyFinishedError`] instead of reaching an
+        /// `unreachable!()` deeper in the resume logic.
+        const GENERATOR_OR_ASYNC_COMPLETED = 0b0001_0000;
     }
 }
 
@@ -65,6 +84,10 @@ pub struct CallFrame {
     pub(crate) local_bindings_initialized: Box<[bool]>,
 
     /// How many iterations a loop has done.
+    ///
+    /// Bumped by [`Vm::advance_loop_iteration`](super::Vm::advance_loop_iteration),
+    /// which is also the single checkpoint that charges fuel (and, once
+    /// configured, checks the epoch deadline) on loop back-edges.
     pub(crate) loop_iteration_count: u64,
 
     /// `[[ScriptOrModule]]`
@@ -272,27 +295,41 @@ impl CallFrame {
             "Only async functions have a promise capability"
         );
 
-        registers.set(
-            Self::PROMISE_CAPABILITY_PROMISE_REGISTER_INDEX,
-            promise_capability
-                .map(PromiseCapability::promise)
-                .cloned()
-                .map_or_else(JsValue::undefined, Into::into),
-        );
-        registers.set(
-            Self::PROMISE_CAPABILITY_RESOLVE_REGISTER_INDEX,
-            promise_capability
-                .map(PromiseCapability::resolve)
-                .cloned()
-                .map_or_else(JsValue::undefined, Into::into),
-        );
-        registers.set(
-            Self::PROMISE_CAPABILITY_REJECT_REGISTER_INDEX,
-            promise_capability
-                .map(PromiseCapability::reject)
-                .cloned()
-                .map_or_else(JsValue::undefined, Into::into),
-        );
+        // These always write within the fixed prologue window every async
+        // frame's register file is pre-sized for (see
+        // `ASYNC_GENERATOR_OBJECT_REGISTER_INDEX`), so the backing store
+        // never actually grows here and there is nothing for a
+        // `ResourceLimiter` to refuse.
+        registers
+            .set(
+                Self::PROMISE_CAPABILITY_PROMISE_REGISTER_INDEX,
+                promise_capability
+                    .map(PromiseCapability::promise)
+                    .cloned()
+                    .map_or_else(JsValue::undefined, Into::into),
+                None,
+            )
+            .expect("writing a pre-allocated promise capability register cannot fail");
+        registers
+            .set(
+                Self::PROMISE_CAPABILITY_RESOLVE_REGISTER_INDEX,
+                promise_capability
+                    .map(PromiseCapability::resolve)
+                    .cloned()
+                    .map_or_else(JsValue::undefined, Into::into),
+                None,
+            )
+            .expect("writing a pre-allocated promise capability register cannot fail");
+        registers
+            .set(
+                Self::PROMISE_CAPABILITY_REJECT_REGISTER_INDEX,
+                promise_capability
+                    .map(PromiseCapability::reject)
+                    .cloned()
+                    .map_or_else(JsValue::undefined, Into::into),
+                None,
+            )
+            .expect("writing a pre-allocated promise capability register cannot fail");
     }
 
     /// Does this have the [`CallFrameFlags::EXIT_EARLY`] flag.
@@ -318,6 +355,23 @@ impl CallFrame {
     pub(crate) fn has_this_value_cached(&self) -> bool {
         self.flags.contains(CallFrameFlags::THIS_VALUE_CACHED)
     }
+    /// Has the generator or async function this [`CallFrame`] belongs to
+    /// already run to completion.
+    ///
+    /// See [`CallFrameFlags::GENERATOR_OR_ASYNC_COMPLETED`].
+    pub(crate) fn generator_or_async_completed(&self) -> bool {
+        self.flags
+            .contains(CallFrameFlags::GENERATOR_OR_ASYNC_COMPLETED)
+    }
+    /// Marks the generator or async function this [`CallFrame`] belongs to
+    /// as having run to completion.
+    ///
+    /// Once set, any further resume attempt on this frame must be rejected
+    /// with a [`GeneratorAlreadyFinishedError`] rather than executed.
+    pub(crate) fn set_generator_or_async_completed(&mut self) {
+        self.flags
+            .insert(CallFrameFlags::GENERATOR_OR_ASYNC_COMPLETED);
+    }
 }
 
 /// ---- `CallFrame` stack methods ----
@@ -325,6 +379,38 @@ impl CallFrame {
     pub(crate) fn set_register_pointer(&mut self, pointer: u32) {
         self.rp = pointer;
     }
+
+    /// Pushes `record` onto [`CallFrame::iterators`], consulting the
+    /// installed [`ResourceLimiter::table_growing`] first.
+    pub(crate) fn push_iterator(
+        &mut self,
+        record: IteratorRecord,
+        limiter: Option<&mut dyn ResourceLimiter>,
+    ) -> JsResult<()> {
+        if let Some(limiter) = limiter {
+            if !limiter.table_growing(self.iterators.len(), self.iterators.len() + 1) {
+                return resource_limit_exceeded_error("iterators");
+            }
+        }
+        self.iterators.push(record);
+        Ok(())
+    }
+
+    /// Pushes `locator` onto the `binding_stack`, consulting the installed
+    /// [`ResourceLimiter::table_growing`] first.
+    pub(crate) fn push_binding(
+        &mut self,
+        locator: BindingLocator,
+        limiter: Option<&mut dyn ResourceLimiter>,
+    ) -> JsResult<()> {
+        if let Some(limiter) = limiter {
+            if !limiter.table_growing(self.binding_stack.len(), self.binding_stack.len() + 1) {
+                return resource_limit_exceeded_error("binding stack");
+            }
+        }
+        self.binding_stack.push(locator);
+        Ok(())
+    }
 }
 
 /// Indicates how a generator function that has been called/resumed should return.
@@ -364,3 +450,139 @@ impl JsValue {
         unreachable!("generator kind must be a integer type")
     }
 }
+
+/// Error produced when attempting to resume a [`CallFrame`] whose generator
+/// or async function has already run to completion.
+///
+/// Mirrors the distinct `GeneratorResumedAfterReturn`/`AsyncResumedAfterReturn`
+/// conditions this engine already needs to surface for fuzzer-found "resume
+/// after completion" cases: instead of relying on an opaque `unreachable!()`
+/// deep in the `async_generator_object`/`promise_capability` resumption
+/// logic, the illegal resume is caught at the [`CallFrame`] boundary (see
+/// [`CallFrame::generator_or_async_completed`]) and reported here with
+/// enough context to diagnose an engine bug rather than panic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GeneratorAlreadyFinishedError {
+    /// A generator was resumed after it had already returned or thrown.
+    Generator,
+    /// An async function's frame was resumed after it had already settled
+    /// its promise capability.
+    AsyncFunction,
+}
+
+impl std::fmt::Display for GeneratorAlreadyFinishedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Generator => {
+                f.write_str("generator was resumed after it already completed")
+            }
+            Self::AsyncFunction => {
+                f.write_str("async function frame was resumed after it already completed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeneratorAlreadyFinishedError {}
+
+/// Decides whether a resume may proceed, kept free of `CallFrame` so the
+/// decision itself is unit-testable without constructing one.
+///
+/// See [`CallFrame::checked_generator_resume_kind`], the real call site.
+fn checked_resume_kind(
+    already_completed: bool,
+    is_async: bool,
+    value: &JsValue,
+) -> Result<GeneratorResumeKind, GeneratorAlreadyFinishedError> {
+    if already_completed {
+        return Err(if is_async {
+            GeneratorAlreadyFinishedError::AsyncFunction
+        } else {
+            GeneratorAlreadyFinishedError::Generator
+        });
+    }
+
+    Ok(value.to_generator_resume_kind())
+}
+
+impl CallFrame {
+    /// Checks whether this frame may legally be resumed, before converting
+    /// `value` to a [`GeneratorResumeKind`].
+    ///
+    /// Returns [`GeneratorAlreadyFinishedError`] instead of proceeding if
+    /// [`CallFrame::generator_or_async_completed`] is already set, so that
+    /// an illegal resume of a finished generator or awaited async frame
+    /// fails loudly with context rather than reaching the `unreachable!()`
+    /// paths in `to_generator_resume_kind` or the resumption logic further
+    /// down the line. Called from [`Vm::resume_top_frame`] in place of a
+    /// bare `to_generator_resume_kind()`.
+    pub(crate) fn checked_generator_resume_kind(
+        &self,
+        value: &JsValue,
+    ) -> Result<GeneratorResumeKind, GeneratorAlreadyFinishedError> {
+        checked_resume_kind(
+            self.generator_or_async_completed(),
+            self.code_block().is_async(),
+            value,
+        )
+    }
+}
+
+#[cfg(test)]
+mod generator_resume_tests {
+    use super::{checked_resume_kind, GeneratorAlreadyFinishedError, GeneratorResumeKind};
+    use crate::JsValue;
+
+    #[test]
+    fn a_completed_generator_rejects_any_resume() {
+        assert_eq!(
+            checked_resume_kind(true, false, &JsValue::new(0)),
+            Err(GeneratorAlreadyFinishedError::Generator)
+        );
+    }
+
+    #[test]
+    fn a_completed_async_frame_rejects_any_resume() {
+        assert_eq!(
+            checked_resume_kind(true, true, &JsValue::new(0)),
+            Err(GeneratorAlreadyFinishedError::AsyncFunction)
+        );
+    }
+
+    #[test]
+    fn an_unfinished_frame_resumes_normally() {
+        assert_eq!(
+            checked_resume_kind(false, false, &JsValue::new(2)),
+            Ok(GeneratorResumeKind::Return)
+        );
+    }
+
+    #[test]
+    fn a_return_resume_kind_does_not_by_itself_mark_the_frame_completed() {
+        // `try { yield 1 } finally { yield 2 }` legitimately yields again
+        // after a `.return()` resume: `already_completed` models the frame's
+        // *actual* completion state (set only once bytecode execution
+        // reaches a terminal return/throw, i.e. by `Vm::complete_top_frame`
+        // rather than by the resume kind requested), so it must stay `false`
+        // across that second, perfectly legal yield.
+        let already_completed = false;
+
+        assert_eq!(
+            checked_resume_kind(already_completed, false, &JsValue::new(2)),
+            Ok(GeneratorResumeKind::Return)
+        );
+        // The `finally` block's `yield 2` resumes the still-unfinished
+        // frame again; this must not have been rejected by the line above.
+        assert_eq!(
+            checked_resume_kind(already_completed, false, &JsValue::new(0)),
+            Ok(GeneratorResumeKind::Normal)
+        );
+
+        // Only once the frame has actually completed does a further resume
+        // get rejected.
+        assert_eq!(
+            checked_resume_kind(true, false, &JsValue::new(0)),
+            Err(GeneratorAlreadyFinishedError::Generator)
+        );
+    }
+}
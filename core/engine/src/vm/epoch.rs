@@ -0,0 +1,207 @@
+//! Epoch-based interruption of a running [`Context`].
+//!
+//! This complements [fuel metering](super::fuel) with a mechanism for
+//! cancelling a script from *another* thread without polling: a shared
+//! counter ("epoch") is bumped by a watchdog, and the interpreter compares
+//! against it at the same cheap checkpoints used for fuel and
+//! [`CallFrame::loop_iteration_count`](super::call_frame::CallFrame) —
+//! loop back-edges and function entry. Unlike fuel, the cost to the hot path
+//! is a single relaxed atomic load rather than an arithmetic decrement, so
+//! the two mechanisms are complementary rather than redundant: fuel bounds
+//! *work done*, epochs bound *wall-clock time*.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use crate::Context;
+
+/// A shared, thread-safe epoch counter.
+///
+/// Cloning an [`EpochHandle`] gives another thread a way to request
+/// interruption of every [`Context`] that was configured with it, via
+/// [`EpochHandle::increment_epoch`].
+#[derive(Debug, Clone, Default)]
+pub struct EpochHandle(Arc<AtomicU64>);
+
+impl EpochHandle {
+    /// Creates a new epoch counter starting at `0`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the epoch by one tick.
+    ///
+    /// Any [`Context`] whose deadline is before the new value will
+    /// trap the next time it checks, without the watchdog needing to know
+    /// which `Context`s exist or what they are doing.
+    pub fn increment_epoch(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn current(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Decision returned by an [`EpochDeadlineCallback`] when the deadline has
+/// passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpochDeadlineDecision {
+    /// Abort execution with [`EpochDeadlineExceeded`].
+    Trap,
+    /// Keep running, having pushed the deadline `ticks` further out.
+    Extend {
+        /// How many additional ticks to allow before checking again.
+        ticks: u64,
+    },
+}
+
+/// A callback consulted when a [`Context`]'s epoch deadline has passed,
+/// deciding whether to abort or grant more time.
+///
+/// This mirrors wasmtime's `Store::epoch_deadline_callback`: it lets a host
+/// make the trap-or-extend decision based on information the VM doesn't
+/// have (e.g. how long the call has already run in wall-clock time).
+pub trait EpochDeadlineCallback: Send + Sync {
+    /// Called once the epoch has advanced past the current deadline.
+    fn on_deadline(&self) -> EpochDeadlineDecision;
+}
+
+/// Per-[`Vm`](super::Vm) epoch deadline state.
+#[derive(Clone, Default)]
+pub(crate) struct EpochDeadline {
+    handle: Option<EpochHandle>,
+    deadline: u64,
+    callback: Option<Arc<dyn EpochDeadlineCallback>>,
+}
+
+impl std::fmt::Debug for EpochDeadline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EpochDeadline")
+            .field("handle", &self.handle)
+            .field("deadline", &self.deadline)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+impl EpochDeadline {
+    /// Creates a deadline watching `handle`, set to `ticks` past its current
+    /// value, preserving any previously installed callback.
+    pub(crate) fn new(handle: EpochHandle, ticks: u64, callback: Option<Arc<dyn EpochDeadlineCallback>>) -> Self {
+        let deadline = handle.current().saturating_add(ticks);
+        Self {
+            handle: Some(handle),
+            deadline,
+            callback,
+        }
+    }
+
+    /// Checks the shared epoch against the configured deadline.
+    ///
+    /// Returns `true` if execution must unwind with
+    /// [`EpochDeadlineExceeded`]; `false` (including when no handle is
+    /// configured) means execution may continue. A single relaxed load
+    /// keeps this cheap enough to call at every loop back-edge and function
+    /// entry. When the deadline has passed and a callback was installed, it
+    /// is given the chance to extend the deadline instead of trapping.
+    #[must_use]
+    pub(crate) fn check(&mut self) -> bool {
+        let Some(handle) = &self.handle else {
+            return false;
+        };
+        if handle.current() <= self.deadline {
+            return false;
+        }
+        match self.callback.as_ref().map(|cb| cb.on_deadline()) {
+            Some(EpochDeadlineDecision::Extend { ticks }) => {
+                self.deadline = handle.current().saturating_add(ticks);
+                false
+            }
+            Some(EpochDeadlineDecision::Trap) | None => true,
+        }
+    }
+}
+
+/// Error returned when a [`Context`]'s epoch deadline has passed.
+///
+/// Like [`FuelExhausted`](super::fuel::FuelExhausted), this is a host-level
+/// trap rather than a catchable JS exception: it unwinds every
+/// [`CallFrame`](super::call_frame::CallFrame) and propagates out of
+/// [`Context::run`](crate::Context::run).
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct EpochDeadlineExceeded;
+
+impl std::fmt::Display for EpochDeadlineExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("epoch deadline exceeded")
+    }
+}
+
+impl std::error::Error for EpochDeadlineExceeded {}
+
+impl Context<'_> {
+    /// Installs the shared epoch counter this context should watch, and sets
+    /// how many ticks from the counter's current value it may run for.
+    ///
+    /// Calling this again replaces both the handle and the deadline.
+    pub fn set_epoch_deadline(&mut self, handle: EpochHandle, ticks: u64) {
+        let callback = self.vm.epoch_deadline.callback.clone();
+        self.vm.epoch_deadline = EpochDeadline::new(handle, ticks, callback);
+    }
+
+    /// Installs a callback to run when the epoch deadline passes, letting
+    /// the host decide whether to extend the deadline or let it trap.
+    ///
+    /// Has no effect until [`Context::set_epoch_deadline`] has also been
+    /// called with a handle to watch.
+    pub fn set_epoch_deadline_callback(&mut self, callback: Arc<dyn EpochDeadlineCallback>) {
+        self.vm.epoch_deadline.callback = Some(callback);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EpochDeadline, EpochDeadlineCallback, EpochDeadlineDecision, EpochHandle};
+    use std::sync::Arc;
+
+    #[test]
+    fn check_is_false_until_the_epoch_passes_the_deadline() {
+        let handle = EpochHandle::new();
+        let mut deadline = EpochDeadline::new(handle.clone(), 2, None);
+
+        handle.increment_epoch();
+        handle.increment_epoch();
+        assert!(!deadline.check(), "epoch == deadline must not trap yet");
+
+        handle.increment_epoch();
+        assert!(deadline.check(), "epoch > deadline must trap");
+    }
+
+    #[test]
+    fn check_is_false_when_no_handle_is_configured() {
+        let mut deadline = EpochDeadline::default();
+        assert!(!deadline.check());
+    }
+
+    struct AlwaysExtend;
+    impl EpochDeadlineCallback for AlwaysExtend {
+        fn on_deadline(&self) -> EpochDeadlineDecision {
+            EpochDeadlineDecision::Extend { ticks: 10 }
+        }
+    }
+
+    #[test]
+    fn callback_can_extend_the_deadline_instead_of_trapping() {
+        let handle = EpochHandle::new();
+        let mut deadline = EpochDeadline::new(handle.clone(), 0, Some(Arc::new(AlwaysExtend)));
+
+        handle.increment_epoch();
+        assert!(!deadline.check());
+        assert_eq!(deadline.deadline, 10);
+    }
+}
@@ -0,0 +1,149 @@
+//! Fuel-based execution metering.
+//!
+//! Fuel gives an embedder a way to bound how much work a script can do,
+//! independent of wall-clock time. It mirrors wasmtime's `Store` fuel model:
+//! a signed counter is consulted at cheap, well-known points in the
+//! interpreter (loop back-edges and function entry, the same sites that
+//! already bump [`CallFrame::loop_iteration_count`][crate::vm::CallFrame]),
+//! and crossing zero unwinds every [`CallFrame`](crate::vm::CallFrame) and
+//! aborts execution with a trap that cannot be caught by a JS `try`/`catch`.
+
+use crate::Context;
+
+/// Fuel remaining for a [`Vm`](super::Vm) to execute.
+///
+/// Stored as a signed counter so that a single "charge" can overshoot past
+/// zero without extra branching in the hot path: callers only need to check
+/// `remaining <= 0` after subtracting a cost, rather than checking before
+/// every subtraction whether it would underflow.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Fuel {
+    /// `None` means fuel metering is disabled; no checks are performed.
+    remaining: Option<i64>,
+}
+
+impl Default for Fuel {
+    fn default() -> Self {
+        Self { remaining: None }
+    }
+}
+
+impl Fuel {
+    /// Enables fuel metering and sets the remaining fuel to `fuel`.
+    pub(crate) fn set(&mut self, fuel: u64) {
+        self.remaining = Some(i64::try_from(fuel).unwrap_or(i64::MAX));
+    }
+
+    /// Adds `fuel` to the remaining amount, enabling metering if it was disabled.
+    pub(crate) fn add(&mut self, fuel: u64) {
+        let fuel = i64::try_from(fuel).unwrap_or(i64::MAX);
+        self.remaining = Some(self.remaining.unwrap_or(0).saturating_add(fuel));
+    }
+
+    /// Returns the remaining fuel, or `None` if metering is disabled.
+    pub(crate) fn remaining(&self) -> Option<u64> {
+        self.remaining.map(|fuel| fuel.max(0) as u64)
+    }
+
+    /// Charges `cost` against the remaining fuel.
+    ///
+    /// Returns `true` if the budget has been exhausted and the interpreter
+    /// must unwind. A no-op (always returns `false`) when metering is
+    /// disabled, so this can sit unconditionally at the existing
+    /// `loop_iteration_count` increment sites without an extra branch for
+    /// the common "fuel disabled" case beyond the `Option` check itself.
+    #[must_use]
+    pub(crate) fn charge(&mut self, cost: u64) -> bool {
+        let Some(remaining) = self.remaining.as_mut() else {
+            return false;
+        };
+        *remaining = remaining.saturating_sub(i64::try_from(cost).unwrap_or(i64::MAX));
+        *remaining <= 0
+    }
+}
+
+/// Error returned when a [`Context`] runs out of fuel.
+///
+/// This is deliberately not a [`JsError`] that JS code can catch: fuel
+/// exhaustion is a host-imposed trap, not a script-level exception, so it
+/// must propagate out of [`Context::run`](crate::Context::run) unconditionally.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct FuelExhausted;
+
+impl std::fmt::Display for FuelExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("fuel exhausted")
+    }
+}
+
+impl std::error::Error for FuelExhausted {}
+
+#[cfg(test)]
+mod tests {
+    use super::Fuel;
+
+    #[test]
+    fn charge_reports_exhaustion_once_the_budget_reaches_zero() {
+        let mut fuel = Fuel::default();
+        fuel.set(3);
+
+        assert!(!fuel.charge(1));
+        assert_eq!(fuel.remaining(), Some(2));
+        assert!(fuel.charge(2));
+        assert_eq!(fuel.remaining(), Some(0));
+        assert!(fuel.charge(1));
+        assert_eq!(fuel.remaining(), Some(0));
+    }
+
+    #[test]
+    fn charge_is_a_no_op_when_metering_is_disabled() {
+        let mut fuel = Fuel::default();
+        assert_eq!(fuel.remaining(), None);
+        assert!(!fuel.charge(u64::MAX));
+        assert_eq!(fuel.remaining(), None);
+    }
+
+    #[test]
+    fn charge_saturates_instead_of_wrapping_when_the_cost_exceeds_the_budget() {
+        let mut fuel = Fuel::default();
+        fuel.set(1);
+
+        // A single charge far larger than what's left must not panic (debug
+        // build) or wrap back around to a positive value (release build) —
+        // it should simply bottom out at an exhausted budget.
+        assert!(fuel.charge(u64::MAX));
+        assert_eq!(fuel.remaining(), Some(0));
+        assert!(fuel.charge(u64::MAX));
+        assert_eq!(fuel.remaining(), Some(0));
+    }
+
+    #[test]
+    fn add_enables_metering_if_it_was_disabled() {
+        let mut fuel = Fuel::default();
+        fuel.add(5);
+        assert_eq!(fuel.remaining(), Some(5));
+    }
+}
+
+impl Context<'_> {
+    /// Sets the remaining fuel for this context, enabling fuel metering.
+    ///
+    /// Subsequent execution will trap once the budget is exhausted, which
+    /// unwinds every [`CallFrame`](crate::vm::CallFrame) on the [`Vm`](super::Vm) stack.
+    pub fn set_fuel(&mut self, fuel: u64) {
+        self.vm.fuel.set(fuel);
+    }
+
+    /// Adds `fuel` to the context's remaining budget, enabling metering if it
+    /// was not already active.
+    pub fn add_fuel(&mut self, fuel: u64) {
+        self.vm.fuel.add(fuel);
+    }
+
+    /// Returns the remaining fuel, or `None` if fuel metering is disabled.
+    #[must_use]
+    pub fn fuel_remaining(&self) -> Option<u64> {
+        self.vm.fuel.remaining()
+    }
+}
@@ -0,0 +1,386 @@
+//! The bytecode virtual machine that drives [`CallFrame`] execution.
+//!
+//! This module owns the parts of the VM that the sandboxing subsystems
+//! ([`fuel`], [`epoch`], [`stack_limit`], [`resource_limiter`]) hook into:
+//! the frame stack, the register file, and the checkpoints where those
+//! subsystems are consulted (advancing a loop for fuel and the epoch
+//! deadline; pushing a frame for the depth limit and the resource limiter).
+
+mod call_frame;
+mod epoch;
+mod fuel;
+mod resource_limiter;
+mod stack_limit;
+
+pub use call_frame::{CallFrame, GeneratorResumeKind};
+pub use epoch::{EpochDeadlineCallback, EpochDeadlineDecision, EpochDeadlineExceeded, EpochHandle};
+pub use fuel::FuelExhausted;
+pub use resource_limiter::ResourceLimiter;
+pub use stack_limit::DEFAULT_MAX_CALL_STACK_DEPTH;
+
+pub(crate) use call_frame::CallFrameFlags;
+
+use call_frame::GeneratorAlreadyFinishedError;
+use epoch::EpochDeadline;
+use fuel::Fuel;
+use resource_limiter::resource_limit_exceeded_error;
+use stack_limit::{call_stack_size_exceeded_error, exceeds_call_stack_limit};
+
+use boa_gc::{Finalize, Trace};
+
+use crate::{JsResult, JsValue};
+
+/// Placeholder for `[[ScriptOrModule]]` linkage tracked per [`CallFrame`].
+///
+/// The full `Script`/`Module` variants live elsewhere in the engine; only
+/// the shape needed to store an optional active runnable on a `CallFrame`
+/// is reproduced here.
+#[derive(Clone, Debug, Finalize, Trace)]
+pub(crate) enum ActiveRunnable {}
+
+/// The register file backing the currently running [`CallFrame`]s.
+#[derive(Clone, Debug, Default, Finalize, Trace)]
+pub(crate) struct Registers {
+    values: Vec<JsValue>,
+}
+
+impl Registers {
+    /// Returns the value at `index`.
+    pub(crate) fn get(&self, index: u32) -> &JsValue {
+        &self.values[index as usize]
+    }
+
+    /// Sets the value at `index`, growing the backing store if needed.
+    ///
+    /// Consults the installed [`ResourceLimiter::stack_growing`] whenever
+    /// the write would grow the backing allocation, raising a catchable
+    /// `RangeError` instead of growing it if the limiter refuses.
+    pub(crate) fn set(
+        &mut self,
+        index: u32,
+        value: JsValue,
+        limiter: Option<&mut dyn ResourceLimiter>,
+    ) -> JsResult<()> {
+        let index = index as usize;
+        if index >= self.values.len() {
+            if let Some(limiter) = limiter {
+                if !limiter.stack_growing(self.values.len(), index + 1) {
+                    return resource_limit_exceeded_error("registers");
+                }
+            }
+            self.values.resize(index + 1, JsValue::undefined());
+        }
+        self.values[index] = value;
+        Ok(())
+    }
+
+    /// The number of registers currently allocated.
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+}
+
+/// Indicates why the interpreter stopped running without producing a normal
+/// completion.
+///
+/// Every variant here is a host-imposed trap, not a catchable JS exception:
+/// it unwinds every [`CallFrame`] on the [`Vm`]'s frame stack and propagates
+/// out of `Context::run` unconditionally.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum VmTrap {
+    /// The [`fuel`] budget was exhausted.
+    FuelExhausted(FuelExhausted),
+    /// The [`epoch`] deadline was reached (and not extended by a callback).
+    EpochDeadlineExceeded(EpochDeadlineExceeded),
+}
+
+/// Decides whether [`Vm::push_frame`] may push one more frame, combining the
+/// call-stack depth limit with the installed [`ResourceLimiter::frames_growing`]
+/// hook.
+///
+/// Kept free of a real [`CallFrame`], so the combined decision
+/// `Vm::push_frame` makes is unit-testable without constructing one.
+fn frame_push_allowed(
+    current_depth: usize,
+    limit: usize,
+    flags: CallFrameFlags,
+    limiter: Option<&mut dyn ResourceLimiter>,
+) -> JsResult<()> {
+    if exceeds_call_stack_limit(current_depth, limit, flags) {
+        return call_stack_size_exceeded_error();
+    }
+
+    if let Some(limiter) = limiter {
+        if !limiter.frames_growing(current_depth, current_depth + 1) {
+            return resource_limit_exceeded_error("call frames");
+        }
+    }
+
+    Ok(())
+}
+
+/// The bytecode virtual machine.
+#[derive(Debug, Finalize, Trace)]
+pub struct Vm {
+    pub(crate) frames: Vec<CallFrame>,
+    pub(crate) stack: Vec<JsValue>,
+    pub(crate) registers: Registers,
+    #[unsafe_ignore_trace]
+    pub(crate) fuel: Fuel,
+    #[unsafe_ignore_trace]
+    pub(crate) epoch_deadline: EpochDeadline,
+    #[unsafe_ignore_trace]
+    pub(crate) max_call_stack_depth: usize,
+    #[unsafe_ignore_trace]
+    pub(crate) resource_limiter: Option<Box<dyn ResourceLimiter>>,
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        Self {
+            frames: Vec::new(),
+            stack: Vec::new(),
+            registers: Registers::default(),
+            fuel: Fuel::default(),
+            epoch_deadline: EpochDeadline::default(),
+            max_call_stack_depth: DEFAULT_MAX_CALL_STACK_DEPTH,
+            resource_limiter: None,
+        }
+    }
+}
+
+impl Vm {
+    /// Pushes a new [`CallFrame`] onto the frame stack.
+    ///
+    /// Raises a catchable `RangeError` instead of pushing the frame if doing
+    /// so would exceed
+    /// [`Context::max_call_stack_depth`](crate::Context::max_call_stack_depth),
+    /// or if the installed [`ResourceLimiter::frames_growing`] refuses the
+    /// growth.
+    pub(crate) fn push_frame(&mut self, frame: CallFrame) -> JsResult<()> {
+        frame_push_allowed(
+            self.frames.len(),
+            self.max_call_stack_depth,
+            frame.flags,
+            self.resource_limiter.as_deref_mut(),
+        )?;
+
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    /// Pushes `value` onto the value stack.
+    ///
+    /// Consults the installed [`ResourceLimiter::stack_growing`] whenever
+    /// the push would grow the backing allocation, raising a catchable
+    /// `RangeError` instead of growing it if the limiter refuses.
+    pub(crate) fn push_stack_value(&mut self, value: JsValue) -> JsResult<()> {
+        if self.stack.len() == self.stack.capacity() {
+            if let Some(limiter) = &mut self.resource_limiter {
+                if !limiter.stack_growing(self.stack.len(), self.stack.len() + 1) {
+                    return resource_limit_exceeded_error("value stack");
+                }
+            }
+        }
+
+        self.stack.push(value);
+        Ok(())
+    }
+
+    /// Pops the topmost [`CallFrame`] off the frame stack.
+    pub(crate) fn pop_frame(&mut self) -> Option<CallFrame> {
+        self.frames.pop()
+    }
+
+    /// The checkpoint shared by every loop back-edge and function entry in
+    /// the interpreter.
+    ///
+    /// Bumps the current frame's [`CallFrame::loop_iteration_count`],
+    /// charges one unit of fuel against the budget set by
+    /// [`Context::set_fuel`](crate::Context::set_fuel), and checks the
+    /// epoch deadline set by
+    /// [`Context::set_epoch_deadline`](crate::Context::set_epoch_deadline).
+    /// Returns a [`VmTrap`] once either limit has been reached; the caller
+    /// is responsible for calling [`Vm::unwind_all_frames`] in response
+    /// rather than treating this like a catchable `JsError`.
+    pub(crate) fn advance_loop_iteration(&mut self) -> Result<(), VmTrap> {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.loop_iteration_count += 1;
+        }
+
+        if self.fuel.charge(1) {
+            return Err(VmTrap::FuelExhausted(FuelExhausted));
+        }
+
+        if self.epoch_deadline.check() {
+            return Err(VmTrap::EpochDeadlineExceeded(EpochDeadlineExceeded));
+        }
+
+        Ok(())
+    }
+
+    /// Unwinds every [`CallFrame`] on the stack in response to a [`VmTrap`].
+    pub(crate) fn unwind_all_frames(&mut self) {
+        self.frames.clear();
+        self.stack.clear();
+    }
+
+    /// Resumes the topmost [`CallFrame`] with `value`, rejecting the resume
+    /// if that frame has already run to completion (see
+    /// [`CallFrame::checked_generator_resume_kind`]) instead of converting
+    /// `value` with the unchecked `to_generator_resume_kind`.
+    ///
+    /// A `Return`/`Throw` [`GeneratorResumeKind`] only says how the caller
+    /// wants the suspended `yield`/`await` treated — it is not itself proof
+    /// the frame's bytecode has finished running. `try { yield 1 } finally {
+    /// yield 2 }` legitimately yields again after a `.return()`/`.throw()`
+    /// resume, so this does *not* mark the frame completed; that is
+    /// [`Vm::complete_top_frame`]'s job, called once the interpreter has
+    /// actually run the frame to a terminal return/throw.
+    pub(crate) fn resume_top_frame(
+        &mut self,
+        value: &JsValue,
+    ) -> Result<GeneratorResumeKind, GeneratorAlreadyFinishedError> {
+        let frame = self
+            .frames
+            .last()
+            .expect("resume_top_frame called with no active frame");
+        frame.checked_generator_resume_kind(value)
+    }
+
+    /// Marks the topmost [`CallFrame`]'s generator or async function as
+    /// having run to completion.
+    ///
+    /// Called by the interpreter once a frame's bytecode actually reaches a
+    /// terminal return/throw (not merely a `yield`/`await` suspension), so
+    /// that any further [`Vm::resume_top_frame`] call on it is rejected with
+    /// [`GeneratorAlreadyFinishedError`] instead of resuming finished work.
+    pub(crate) fn complete_top_frame(&mut self) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.set_generator_or_async_completed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance_loop_iteration_traps_once_fuel_is_exhausted() {
+        let mut vm = Vm::default();
+        vm.fuel.set(2);
+
+        assert!(vm.advance_loop_iteration().is_ok());
+        assert!(matches!(
+            vm.advance_loop_iteration(),
+            Err(VmTrap::FuelExhausted(_))
+        ));
+    }
+
+    #[test]
+    fn advance_loop_iteration_is_a_no_op_when_fuel_is_disabled() {
+        let mut vm = Vm::default();
+
+        for _ in 0..1000 {
+            assert!(vm.advance_loop_iteration().is_ok());
+        }
+    }
+
+    #[test]
+    fn push_stack_value_rejects_once_the_resource_limiter_refuses_growth() {
+        struct RefuseGrowth;
+        impl ResourceLimiter for RefuseGrowth {
+            fn stack_growing(&mut self, _current: usize, _requested: usize) -> bool {
+                false
+            }
+        }
+
+        let mut vm = Vm::default();
+        vm.resource_limiter = Some(Box::new(RefuseGrowth));
+
+        assert!(vm.push_stack_value(JsValue::undefined()).is_err());
+        assert_eq!(vm.stack.len(), 0);
+    }
+
+    #[test]
+    fn registers_set_rejects_once_the_resource_limiter_refuses_growth() {
+        struct RefuseGrowth;
+        impl ResourceLimiter for RefuseGrowth {
+            fn stack_growing(&mut self, _current: usize, _requested: usize) -> bool {
+                false
+            }
+        }
+
+        let mut registers = Registers::default();
+        let mut limiter = RefuseGrowth;
+        assert!(registers
+            .set(0, JsValue::undefined(), Some(&mut limiter))
+            .is_err());
+        assert_eq!(registers.len(), 0);
+    }
+
+    #[test]
+    fn registers_set_does_not_consult_the_limiter_when_not_growing() {
+        struct RefuseGrowth;
+        impl ResourceLimiter for RefuseGrowth {
+            fn stack_growing(&mut self, _current: usize, _requested: usize) -> bool {
+                false
+            }
+        }
+
+        let mut registers = Registers::default();
+        registers.set(0, JsValue::undefined(), None).unwrap();
+
+        let mut limiter = RefuseGrowth;
+        assert!(registers
+            .set(0, JsValue::undefined(), Some(&mut limiter))
+            .is_ok());
+    }
+
+    #[test]
+    fn frame_push_allowed_rejects_once_the_depth_limit_is_reached() {
+        assert!(frame_push_allowed(9, 10, CallFrameFlags::empty(), None).is_ok());
+        assert!(frame_push_allowed(10, 10, CallFrameFlags::empty(), None).is_err());
+    }
+
+    #[test]
+    fn frame_push_allowed_rejects_once_the_resource_limiter_refuses_growth() {
+        struct RefuseGrowth;
+        impl ResourceLimiter for RefuseGrowth {
+            fn frames_growing(&mut self, _current: usize, _requested: usize) -> bool {
+                false
+            }
+        }
+
+        let mut limiter = RefuseGrowth;
+        assert!(
+            frame_push_allowed(0, 10, CallFrameFlags::empty(), Some(&mut limiter)).is_err()
+        );
+    }
+
+    #[test]
+    fn frame_push_allowed_checks_the_depth_limit_even_if_the_limiter_would_allow_growth() {
+        struct AlwaysAllow;
+        impl ResourceLimiter for AlwaysAllow {}
+
+        let mut limiter = AlwaysAllow;
+        assert!(
+            frame_push_allowed(10, 10, CallFrameFlags::empty(), Some(&mut limiter)).is_err()
+        );
+    }
+
+    #[test]
+    fn advance_loop_iteration_traps_once_the_epoch_deadline_passes() {
+        let handle = EpochHandle::new();
+        let mut vm = Vm::default();
+        vm.epoch_deadline = EpochDeadline::new(handle.clone(), 0, None);
+
+        assert!(vm.advance_loop_iteration().is_ok());
+        handle.increment_epoch();
+        assert!(matches!(
+            vm.advance_loop_iteration(),
+            Err(VmTrap::EpochDeadlineExceeded(_))
+        ));
+    }
+}
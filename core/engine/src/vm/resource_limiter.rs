@@ -0,0 +1,116 @@
+//! Pluggable resource limiting for bounded VM growth.
+//!
+//! [`fuel`](super::fuel) and [`epoch`](super::epoch) bound *time*;
+//! [`stack_limit`](super::stack_limit) bounds call *depth*. None of them say
+//! anything about *memory*. [`ResourceLimiter`] fills that gap: the VM
+//! consults it before growing anything unbounded by a fixed, cheap-to-check
+//! count — a new [`CallFrame`](super::call_frame::CallFrame), the value
+//! stack/[`Registers`](super::Registers) backing store, or a large
+//! iterator/binding buffer
+//! ([`CallFrame::iterators`](super::call_frame::CallFrame::iterators),
+//! `binding_stack`) — so a refusal becomes a graceful JS error rather than
+//! an allocation panic or an unbounded host memory grab.
+
+use crate::{Context, JsNativeError, JsResult};
+
+/// A hook an embedder installs on a [`Context`] to police growth of
+/// VM-managed, memory-bounded resources.
+///
+/// Modeled on wasmtime's store resource limiter: every callback receives the
+/// resource's current size and the size being requested, and returns
+/// whether the growth is allowed. This gives a single cross-cutting policy
+/// surface for memory-bounded sandboxes (e.g. many small [`Context`]s
+/// sharing one host process), distinct from the time-based limits in
+/// [`fuel`](super::fuel) and [`epoch`](super::epoch).
+pub trait ResourceLimiter {
+    /// Called before pushing a new [`CallFrame`](super::call_frame::CallFrame)
+    /// onto the [`Vm`](super::Vm)'s frame stack.
+    ///
+    /// `current` and `requested` are frame counts. Returning `false` raises
+    /// a catchable `RangeError` at the call site instead of growing the
+    /// frame stack.
+    fn frames_growing(&mut self, current: usize, requested: usize) -> bool {
+        let _ = (current, requested);
+        true
+    }
+
+    /// Called before growing the value stack / [`Registers`](super::Registers)
+    /// backing store.
+    ///
+    /// `current` and `requested` are element counts (not bytes). Returning
+    /// `false` raises a catchable `RangeError` instead of growing the stack.
+    fn stack_growing(&mut self, current: usize, requested: usize) -> bool {
+        let _ = (current, requested);
+        true
+    }
+
+    /// Called before growing a large iterator or binding buffer, such as
+    /// [`CallFrame::iterators`](super::call_frame::CallFrame::iterators) or
+    /// its `binding_stack`.
+    ///
+    /// `current` and `requested` are element counts. Returning `false`
+    /// raises a catchable `RangeError` instead of growing the buffer.
+    fn table_growing(&mut self, current: usize, requested: usize) -> bool {
+        let _ = (current, requested);
+        true
+    }
+}
+
+/// Returns the `RangeError` raised when a [`ResourceLimiter`] refuses to let
+/// a bounded resource grow.
+pub(crate) fn resource_limit_exceeded_error(resource: &str) -> JsResult<()> {
+    Err(JsNativeError::range()
+        .with_message(format!("resource limit exceeded while growing {resource}"))
+        .into())
+}
+
+impl Context<'_> {
+    /// Installs a [`ResourceLimiter`] that the [`Vm`](super::Vm) consults
+    /// before growing bounded resources (call frames, the value stack,
+    /// iterator/binding buffers).
+    ///
+    /// Replaces any limiter previously installed on this context.
+    pub fn set_resource_limiter(&mut self, limiter: Box<dyn ResourceLimiter>) {
+        self.vm.resource_limiter = Some(limiter);
+    }
+
+    /// Removes any [`ResourceLimiter`] previously installed on this context.
+    pub fn clear_resource_limiter(&mut self) {
+        self.vm.resource_limiter = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resource_limit_exceeded_error, ResourceLimiter};
+
+    #[derive(Default)]
+    struct RefuseAfter {
+        allowed: usize,
+    }
+
+    impl ResourceLimiter for RefuseAfter {
+        fn frames_growing(&mut self, _current: usize, requested: usize) -> bool {
+            requested <= self.allowed
+        }
+    }
+
+    #[test]
+    fn default_callbacks_always_allow_growth() {
+        struct Permissive;
+        impl ResourceLimiter for Permissive {}
+
+        let mut limiter = Permissive;
+        assert!(limiter.frames_growing(0, 1_000_000));
+        assert!(limiter.stack_growing(0, 1_000_000));
+        assert!(limiter.table_growing(0, 1_000_000));
+    }
+
+    #[test]
+    fn a_refusal_is_reported_as_a_resource_limit_error() {
+        let mut limiter = RefuseAfter { allowed: 2 };
+        assert!(limiter.frames_growing(1, 2));
+        assert!(!limiter.frames_growing(2, 3));
+        assert!(resource_limit_exceeded_error("call frames").is_err());
+    }
+}
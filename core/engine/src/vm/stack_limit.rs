@@ -0,0 +1,102 @@
+//! Configurable call-stack depth limit.
+//!
+//! Nothing previously capped how many [`CallFrame`]s a [`Vm`](super::Vm)
+//! could push, so sufficiently deep or mutually recursive JS risked
+//! overflowing the *native* Rust stack rather than failing gracefully.
+//! [`Vm::push_frame`](super::Vm::push_frame) now checks the frame count
+//! against a configurable limit and raises a catchable `RangeError` instead.
+
+use super::CallFrameFlags;
+use crate::{context::ContextBuilder, Context, JsNativeError, JsResult};
+
+/// The default maximum number of [`CallFrame`]s a [`Vm`](super::Vm) will
+/// allow to be pushed before raising a `RangeError`.
+///
+/// Chosen conservatively relative to the default native thread stack size;
+/// embedders running untrusted code on threads with a smaller stack should
+/// lower this via [`ContextBuilder::max_call_stack_depth`].
+pub const DEFAULT_MAX_CALL_STACK_DEPTH: usize = 10_000;
+
+/// Returns the "Maximum call stack size exceeded" error raised when
+/// [`Vm::push_frame`](super::Vm::push_frame) would exceed the configured
+/// depth limit.
+///
+/// A frame with [`CallFrameFlags::REGISTERS_ALREADY_PUSHED`] set (tail calls
+/// and resumed generator/async frames that reuse an existing register
+/// window) still counts as one more frame towards the limit: it is a
+/// logical call just as much as a fresh one, even though it doesn't grow the
+/// register stack the same way.
+pub(crate) fn call_stack_size_exceeded_error() -> JsResult<()> {
+    Err(JsNativeError::range()
+        .with_message("Maximum call stack size exceeded")
+        .into())
+}
+
+/// Returns whether pushing one more frame (with the given flags) would
+/// exceed `limit`, given `current_depth` frames already on the stack.
+#[must_use]
+pub(crate) fn exceeds_call_stack_limit(
+    current_depth: usize,
+    limit: usize,
+    flags: CallFrameFlags,
+) -> bool {
+    // Frames that reuse an already-pushed register window are still a
+    // distinct logical call (tail call / resumed generator or async frame),
+    // so they are counted identically to a fresh frame rather than exempted.
+    let _ = flags;
+    current_depth >= limit
+}
+
+impl Context<'_> {
+    /// Returns the maximum number of [`CallFrame`]s this context will allow
+    /// on its [`Vm`](super::Vm) stack before raising a `RangeError`.
+    #[must_use]
+    pub fn max_call_stack_depth(&self) -> usize {
+        self.vm.max_call_stack_depth
+    }
+
+    /// Sets the maximum number of [`CallFrame`]s this context will allow on
+    /// its [`Vm`](super::Vm) stack before raising a `RangeError`.
+    ///
+    /// Embedders running untrusted code on a thread with a smaller native
+    /// stack than the default should lower this accordingly.
+    /// Also settable at construction time via
+    /// [`ContextBuilder::max_call_stack_depth`], for embedders that want the
+    /// limit in place before any script runs.
+    pub fn set_max_call_stack_depth(&mut self, limit: usize) {
+        self.vm.max_call_stack_depth = limit;
+    }
+}
+
+impl ContextBuilder {
+    /// Configures the maximum number of [`CallFrame`](super::CallFrame)s the
+    /// resulting [`Context`] will allow on its [`Vm`](super::Vm) stack
+    /// before raising a `RangeError`, instead of the default
+    /// [`DEFAULT_MAX_CALL_STACK_DEPTH`].
+    #[must_use]
+    pub fn max_call_stack_depth(mut self, limit: usize) -> Self {
+        self.max_call_stack_depth = Some(limit);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exceeds_call_stack_limit, CallFrameFlags};
+
+    #[test]
+    fn exceeds_call_stack_limit_trips_at_the_limit() {
+        assert!(!exceeds_call_stack_limit(9, 10, CallFrameFlags::empty()));
+        assert!(exceeds_call_stack_limit(10, 10, CallFrameFlags::empty()));
+        assert!(exceeds_call_stack_limit(11, 10, CallFrameFlags::empty()));
+    }
+
+    #[test]
+    fn exceeds_call_stack_limit_counts_reused_register_windows_too() {
+        assert!(exceeds_call_stack_limit(
+            10,
+            10,
+            CallFrameFlags::REGISTERS_ALREADY_PUSHED
+        ));
+    }
+}